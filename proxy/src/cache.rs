@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use once_cell::sync::Lazy;
+use reqwest::header;
+use tokio::sync::RwLock;
+
+/// Freshness window used when upstream doesn't send a `Cache-Control: max-age`.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// A cached upstream response, along with enough validator state to issue a conditional
+/// revalidation request once it goes stale.
+struct CacheEntry {
+    body: Bytes,
+    content_type: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fresh_until: Instant,
+}
+
+static CACHE: Lazy<RwLock<HashMap<String, CacheEntry>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The body and content type served to the client, along with how long it may be cached.
+pub struct CachedResponse {
+    pub body: Bytes,
+    pub content_type: Option<String>,
+    pub max_age: Duration,
+    /// Whether this was served from an already-fresh cache entry, without talking to
+    /// upstream at all.
+    pub cache_hit: bool,
+}
+
+/// Upstream fetch failures, classified so the handler can map each to the right status
+/// code instead of collapsing everything into a bare `502`.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The request to upstream timed out.
+    Timeout,
+    /// Any other failure talking to upstream (DNS, connection refused, TLS, a broken
+    /// response body, ...).
+    Connect(reqwest::Error),
+    /// Upstream answered with a non-2xx status.
+    Upstream(reqwest::StatusCode),
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Timeout => write!(f, "upstream request timed out"),
+            FetchError::Connect(err) => write!(f, "upstream connection failed: {err}"),
+            FetchError::Upstream(status) => write!(f, "upstream returned {status}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Fetches `url` through the shared cache: a fresh entry is served directly, a stale one is
+/// revalidated with `If-None-Match`/`If-Modified-Since` and reused on a `304`, and a cache
+/// miss falls back to a plain `GET`.
+pub async fn fetch_cached(client: &reqwest::Client, url: &str) -> Result<CachedResponse, FetchError> {
+    if let Some(entry) = CACHE.read().await.get(url) {
+        if entry.fresh_until > Instant::now() {
+            return Ok(to_response(entry, true));
+        }
+    }
+
+    let mut req = client.get(url);
+    if let Some(entry) = CACHE.read().await.get(url) {
+        if let Some(etag) = &entry.etag {
+            req = req.header(header::IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            req = req.header(header::IF_MODIFIED_SINCE, last_modified.clone());
+        }
+    }
+
+    let mut resp = req.send().await.map_err(classify_send_error)?;
+    let mut max_age = max_age_of(resp.headers());
+    let mut etag = header_string(resp.headers(), header::ETAG);
+    let mut last_modified = header_string(resp.headers(), header::LAST_MODIFIED);
+
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        let mut cache = CACHE.write().await;
+        if let Some(entry) = cache.get_mut(url) {
+            entry.fresh_until = Instant::now() + max_age;
+            if etag.is_some() {
+                entry.etag = etag;
+            }
+            if last_modified.is_some() {
+                entry.last_modified = last_modified;
+            }
+            return Ok(to_response(entry, false));
+        }
+        // No prior entry to revalidate against; fall back to a plain re-fetch.
+        drop(cache);
+        resp = client.get(url).send().await.map_err(classify_send_error)?;
+        max_age = max_age_of(resp.headers());
+        etag = header_string(resp.headers(), header::ETAG);
+        last_modified = header_string(resp.headers(), header::LAST_MODIFIED);
+    }
+    if !resp.status().is_success() {
+        return Err(FetchError::Upstream(resp.status()));
+    }
+
+    let content_type = header_string(resp.headers(), header::CONTENT_TYPE);
+    let body = resp.bytes().await.map_err(classify_send_error)?;
+
+    let entry = CacheEntry {
+        body,
+        content_type,
+        etag,
+        last_modified,
+        fresh_until: Instant::now() + max_age,
+    };
+    let response = to_response(&entry, false);
+    CACHE.write().await.insert(url.to_string(), entry);
+    Ok(response)
+}
+
+fn classify_send_error(err: reqwest::Error) -> FetchError {
+    if err.is_timeout() {
+        FetchError::Timeout
+    } else {
+        FetchError::Connect(err)
+    }
+}
+
+fn to_response(entry: &CacheEntry, cache_hit: bool) -> CachedResponse {
+    CachedResponse {
+        body: entry.body.clone(),
+        content_type: entry.content_type.clone(),
+        max_age: entry.fresh_until.saturating_duration_since(Instant::now()),
+        cache_hit,
+    }
+}
+
+fn header_string(headers: &reqwest::header::HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers.get(name)?.to_str().ok().map(str::to_string)
+}
+
+/// Parses `max-age` out of an upstream `Cache-Control` header. Falls back to the `Expires`
+/// header (an HTTP-date, parsed with `httpdate`) relative to the upstream `Date`, and
+/// finally to [`DEFAULT_MAX_AGE`] when neither is present or parsable.
+fn max_age_of(headers: &reqwest::header::HeaderMap) -> Duration {
+    let from_cache_control = headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.split(',').find_map(|directive| {
+                directive
+                    .trim()
+                    .strip_prefix("max-age=")
+                    .and_then(|secs| secs.parse::<u64>().ok())
+            })
+        })
+        .map(Duration::from_secs);
+
+    from_cache_control
+        .or_else(|| max_age_from_expires(headers))
+        .unwrap_or(DEFAULT_MAX_AGE)
+}
+
+fn max_age_from_expires(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let expires = httpdate::parse_http_date(headers.get(header::EXPIRES)?.to_str().ok()?).ok()?;
+    let date = headers
+        .get(header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| httpdate::parse_http_date(v).ok())
+        .unwrap_or_else(std::time::SystemTime::now);
+    Some(expires.duration_since(date).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn max_age_of_reads_cache_control_max_age() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=300"),
+        );
+
+        assert_eq!(max_age_of(&headers), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn max_age_of_falls_back_to_expires_minus_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::DATE, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        headers.insert(
+            header::EXPIRES,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:33:00 GMT"),
+        );
+
+        assert_eq!(max_age_of(&headers), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn max_age_of_treats_already_expired_expires_as_zero() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::DATE, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        headers.insert(
+            header::EXPIRES,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:23:00 GMT"),
+        );
+
+        assert_eq!(max_age_of(&headers), Duration::ZERO);
+    }
+
+    #[test]
+    fn max_age_of_defaults_when_no_freshness_headers_present() {
+        let headers = HeaderMap::new();
+
+        assert_eq!(max_age_of(&headers), DEFAULT_MAX_AGE);
+    }
+
+    #[test]
+    fn max_age_of_ignores_unparsable_cache_control() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+        assert_eq!(max_age_of(&headers), DEFAULT_MAX_AGE);
+    }
+}
@@ -0,0 +1,49 @@
+/// Feed key served by the bare `/dhmz` alias.
+pub const DEFAULT_FEED: &str = "hrvatska1";
+
+/// A known DHMZ feed: its upstream XML document, and whether `xml::parse` understands its
+/// schema well enough to offer JSON negotiation for it.
+struct Feed {
+    url: &'static str,
+    supports_json: bool,
+}
+
+/// Known DHMZ feed keys. Only `hrvatska1` has a matching [`crate::xml::Forecast`] schema
+/// today, so the others are XML-only until a parser is added for them.
+const FEEDS: &[(&str, Feed)] = &[
+    (
+        "hrvatska1",
+        Feed {
+            url: "https://vrijeme.hr/hrvatska1_n.xml",
+            supports_json: true,
+        },
+    ),
+    (
+        "more",
+        Feed {
+            url: "https://vrijeme.hr/more_n.xml",
+            supports_json: false,
+        },
+    ),
+    (
+        "upozorenja",
+        Feed {
+            url: "https://vrijeme.hr/upozorenja_n.xml",
+            supports_json: false,
+        },
+    ),
+];
+
+/// Looks up the upstream URL for a feed key, or `None` if it isn't one we know about.
+pub fn url_for(feed: &str) -> Option<&'static str> {
+    find(feed).map(|f| f.url)
+}
+
+/// Whether `feed` has a JSON schema it can be negotiated into.
+pub fn supports_json(feed: &str) -> bool {
+    find(feed).is_some_and(|f| f.supports_json)
+}
+
+fn find(feed: &str) -> Option<&'static Feed> {
+    FEEDS.iter().find(|(key, _)| *key == feed).map(|(_, f)| f)
+}
@@ -1,28 +1,290 @@
+mod cache;
+mod feeds;
+mod xml;
+
 use axum::{
-    http::{header, StatusCode},
+    extract::{Path, Query},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
-    Router,
+    Json, Router,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha1::Sha1;
+use std::time::Instant;
+use tower_http::trace::TraceLayer;
+
+/// Hosts the generic proxy is willing to fetch from, even with a valid signature.
+const ALLOWED_HOSTS: &[&str] = &["vrijeme.hr"];
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// Upper bound on a whole upstream request, so a hung or slow-to-respond upstream turns
+/// into a `504` instead of blocking the handler indefinitely.
+const UPSTREAM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Upper bound on establishing the TCP/TLS connection itself.
+const UPSTREAM_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(UPSTREAM_TIMEOUT)
+        .connect_timeout(UPSTREAM_CONNECT_TIMEOUT)
+        .build()
+        .expect("failed to build upstream HTTP client")
+});
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/dhmz", get(proxy));
+    tracing_subscriber::fmt::init();
+
+    let app = Router::new()
+        .route("/dhmz", get(dhmz))
+        .route("/dhmz/:feed", get(dhmz_feed))
+        .route("/proxy/:digest/:encoded_url", get(proxy))
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .layer(TraceLayer::new_for_http());
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
-}
-
-async fn proxy() -> Response {
-    match reqwest::get("https://vrijeme.hr/hrvatska1_n.xml").await {
-        Ok(r) => (
-            StatusCode::OK,
-            [
-                (header::CONTENT_TYPE, "text/xml"),
-                (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
-            ],
-            r.bytes().await.unwrap_or_default(),
-        )
-            .into_response(),
-        Err(_) => StatusCode::BAD_GATEWAY.into_response(),
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+}
+
+/// Liveness probe: if the process can answer at all, it's alive.
+async fn health() -> &'static str {
+    "ok"
+}
+
+/// Readiness probe: additionally checks that the default upstream feed is reachable, so
+/// orchestrators can hold off sending traffic until DHMZ itself is up. Goes through the
+/// shared cache rather than a live request, since orchestrators poll this frequently and
+/// it shouldn't re-hammer upstream on every check.
+async fn ready() -> Response {
+    let url = feeds::url_for(feeds::DEFAULT_FEED).expect("default feed is always registered");
+    match cache::fetch_cached(&HTTP_CLIENT, url).await {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+/// Resolves once a `SIGINT` (Ctrl-C) or, on Unix, a `SIGTERM` is received, so `main` can
+/// drain in-flight requests before shutting the listener down.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[derive(Deserialize)]
+struct FormatQuery {
+    format: Option<String>,
+}
+
+/// Alias for the default national feed, kept for backwards compatibility.
+async fn dhmz(headers: HeaderMap, query: Query<FormatQuery>) -> Response {
+    dhmz_feed(Path(feeds::DEFAULT_FEED.to_string()), headers, query).await
+}
+
+/// Serves a named DHMZ feed as-is, or as normalized JSON when the client asks for it via
+/// `Accept: application/json` or `?format=json`. Unknown feed keys are a `404`; a JSON
+/// request for a feed without a matching schema is a `406` rather than silently returning
+/// an empty/wrong `Forecast`.
+async fn dhmz_feed(
+    Path(feed): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<FormatQuery>,
+) -> Response {
+    let Some(url) = feeds::url_for(&feed) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let want_json = query.format.as_deref() == Some("json") || accepts_json(&headers);
+    if want_json && !feeds::supports_json(&feed) {
+        return StatusCode::NOT_ACCEPTABLE.into_response();
+    }
+    fetch_and_respond(&feed, url, want_json).await
+}
+
+fn accepts_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+/// Generic signature-gated upstream proxy.
+///
+/// `encoded_url` is the base64url (no padding) encoding of the upstream URL, and `digest`
+/// is the hex-encoded HMAC-SHA1 of the raw (decoded) URL, keyed with `PROXY_SECRET`. This
+/// lets a front-end embed a handful of pre-signed upstream endpoints without exposing a
+/// relay that will fetch anything an attacker asks it to.
+async fn proxy(Path((digest, encoded_url)): Path<(String, String)>) -> Response {
+    let Ok(url_bytes) = URL_SAFE_NO_PAD.decode(encoded_url) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Ok(url) = String::from_utf8(url_bytes) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    if !verify_digest(&url, &digest) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    match url::Url::parse(&url) {
+        Ok(parsed) if parsed.host_str().is_some_and(|h| ALLOWED_HOSTS.contains(&h)) => {}
+        _ => return StatusCode::FORBIDDEN.into_response(),
+    }
+
+    fetch_and_respond("proxy", &url, false).await
+}
+
+/// Recomputes `HMAC-SHA1(PROXY_SECRET, url)` and compares it to `digest` (hex-encoded) in
+/// constant time.
+fn verify_digest(url: &str, digest: &str) -> bool {
+    let Ok(secret) = std::env::var("PROXY_SECRET") else {
+        return false;
+    };
+    digest_matches(secret.as_bytes(), url, digest)
+}
+
+/// Core of [`verify_digest`], with the secret passed in directly so it can be unit tested
+/// without touching the process environment.
+fn digest_matches(secret: &[u8], url: &str, digest: &str) -> bool {
+    let Ok(expected) = hex::decode(digest) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha1::new_from_slice(secret) else {
+        return false;
+    };
+    mac.update(url.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Fetches `url` (through the shared cache) and renders it as the response body, tracing
+/// a span per call with the feed, cache hit/miss, and upstream latency so operators can see
+/// what the proxy actually did.
+#[tracing::instrument(
+    skip(url, want_json),
+    fields(feed = %label, cache_hit = tracing::field::Empty, upstream_latency_ms = tracing::field::Empty)
+)]
+async fn fetch_and_respond(label: &str, url: &str, want_json: bool) -> Response {
+    let started_at = Instant::now();
+    let cached = match cache::fetch_cached(&HTTP_CLIENT, url).await {
+        Ok(cached) => cached,
+        Err(err) => {
+            let status = match &err {
+                cache::FetchError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+                cache::FetchError::Connect(_) => StatusCode::BAD_GATEWAY,
+                cache::FetchError::Upstream(status) => {
+                    StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY)
+                }
+            };
+            tracing::warn!(%status, error = %err, "upstream fetch failed");
+            return status.into_response();
+        }
+    };
+    tracing::Span::current()
+        .record("cache_hit", cached.cache_hit)
+        .record("upstream_latency_ms", started_at.elapsed().as_millis() as u64);
+
+    let cache_control = HeaderValue::from_str(&format!(
+        "public, max-age={}",
+        cached.max_age.as_secs()
+    ))
+    .unwrap_or_else(|_| HeaderValue::from_static("no-cache"));
+
+    if want_json {
+        return match xml::parse(&cached.body) {
+            Ok(forecast) => (
+                StatusCode::OK,
+                [
+                    (header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*")),
+                    (header::CACHE_CONTROL, cache_control),
+                ],
+                Json(forecast),
+            )
+                .into_response(),
+            Err(_) => StatusCode::BAD_GATEWAY.into_response(),
+        };
+    }
+
+    let content_type = cached.content_type.unwrap_or_else(|| "text/xml".to_string());
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(&content_type)
+                    .unwrap_or_else(|_| HeaderValue::from_static("text/xml")),
+            ),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*")),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        cached.body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest_hex(secret: &[u8], url: &str) -> String {
+        let mut mac = HmacSha1::new_from_slice(secret).unwrap();
+        mac.update(url.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn digest_matches_accepts_correct_signature() {
+        let secret = b"top-secret";
+        let url = "https://vrijeme.hr/hrvatska1_n.xml";
+        let digest = digest_hex(secret, url);
+
+        assert!(digest_matches(secret, url, &digest));
+    }
+
+    #[test]
+    fn digest_matches_rejects_wrong_secret() {
+        let url = "https://vrijeme.hr/hrvatska1_n.xml";
+        let digest = digest_hex(b"top-secret", url);
+
+        assert!(!digest_matches(b"wrong-secret", url, &digest));
+    }
+
+    #[test]
+    fn digest_matches_rejects_tampered_url() {
+        let secret = b"top-secret";
+        let digest = digest_hex(secret, "https://vrijeme.hr/hrvatska1_n.xml");
+
+        assert!(!digest_matches(secret, "https://evil.example/payload", &digest));
+    }
+
+    #[test]
+    fn digest_matches_rejects_malformed_digest() {
+        let secret = b"top-secret";
+        let url = "https://vrijeme.hr/hrvatska1_n.xml";
+
+        assert!(!digest_matches(secret, url, "not-hex"));
     }
 }
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Parsed form of `hrvatska1_n.xml`: one entry per observing station.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Forecast {
+    #[serde(rename = "Grad", default)]
+    pub stations: Vec<Station>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Station {
+    #[serde(rename = "GradIme")]
+    pub name: String,
+    #[serde(rename = "Lon")]
+    pub lon: f64,
+    #[serde(rename = "Lat")]
+    pub lat: f64,
+    #[serde(rename = "Temp")]
+    pub temperature: Option<f64>,
+    #[serde(rename = "Vlaga")]
+    pub humidity: Option<f64>,
+    #[serde(rename = "Tlak")]
+    pub pressure: Option<f64>,
+    #[serde(rename = "VjetarSmjer")]
+    pub wind_direction: Option<String>,
+    #[serde(rename = "VjetarBrzina")]
+    pub wind_speed: Option<f64>,
+    #[serde(rename = "Vrijeme")]
+    pub condition: Option<String>,
+}
+
+/// Parses a `hrvatska1_n.xml`-shaped document into [`Forecast`].
+pub fn parse(xml: &[u8]) -> Result<Forecast, quick_xml::de::DeError> {
+    quick_xml::de::from_reader(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        <Hrvatska1>
+          <Grad>
+            <GradIme>Zagreb</GradIme>
+            <Lon>15.9819</Lon>
+            <Lat>45.8131</Lat>
+            <Temp>25.3</Temp>
+            <Vlaga>45</Vlaga>
+            <Tlak>1013.2</Tlak>
+            <VjetarSmjer>S</VjetarSmjer>
+            <VjetarBrzina>10</VjetarBrzina>
+            <Vrijeme>Sunny</Vrijeme>
+          </Grad>
+          <Grad>
+            <GradIme>Split</GradIme>
+            <Lon>16.4402</Lon>
+            <Lat>43.5081</Lat>
+          </Grad>
+        </Hrvatska1>
+    "#;
+
+    #[test]
+    fn parses_stations_with_full_data() {
+        let forecast = parse(SAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(forecast.stations.len(), 2);
+        let zagreb = &forecast.stations[0];
+        assert_eq!(zagreb.name, "Zagreb");
+        assert_eq!(zagreb.lat, 45.8131);
+        assert_eq!(zagreb.temperature, Some(25.3));
+        assert_eq!(zagreb.humidity, Some(45.0));
+        assert_eq!(zagreb.pressure, Some(1013.2));
+        assert_eq!(zagreb.wind_direction.as_deref(), Some("S"));
+        assert_eq!(zagreb.condition.as_deref(), Some("Sunny"));
+    }
+
+    #[test]
+    fn tolerates_stations_missing_optional_fields() {
+        let forecast = parse(SAMPLE.as_bytes()).unwrap();
+
+        let split = &forecast.stations[1];
+        assert_eq!(split.name, "Split");
+        assert_eq!(split.temperature, None);
+        assert_eq!(split.condition, None);
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse(b"<not-xml").is_err());
+    }
+}